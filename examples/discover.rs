@@ -1,10 +1,11 @@
 use anyhow::Result;
 use libp2p::dns::TokioDnsConfig;
 use libp2p::futures::StreamExt;
-use libp2p::rendezvous::{Config, Namespace, Rendezvous};
+use libp2p::rendezvous::client::{Behaviour, Event};
+use libp2p::rendezvous::Namespace;
 use libp2p::swarm::{SwarmBuilder, SwarmEvent};
 use libp2p::tcp::TokioTcpConfig;
-use libp2p::{identity, rendezvous, Multiaddr, PeerId, Transport};
+use libp2p::{identity, Multiaddr, PeerId, Transport};
 use rendezvous_server::transport::authenticate_and_multiplex;
 use structopt::StructOpt;
 
@@ -31,7 +32,7 @@ async fn main() -> Result<()> {
 
     let transport = authenticate_and_multiplex(tcp_with_dns.boxed(), &identity).unwrap();
 
-    let rendezvous = Rendezvous::new(identity.clone(), Config::default());
+    let rendezvous = Behaviour::new(identity.clone());
 
     let peer_id = PeerId::from(identity.public());
 
@@ -69,7 +70,7 @@ async fn main() -> Result<()> {
                     address, error
                 );
             }
-            SwarmEvent::Behaviour(rendezvous::Event::Discovered { registrations, .. }) => {
+            SwarmEvent::Behaviour(Event::Discovered { registrations, .. }) => {
                 for registration in registrations {
                     for address in registration.record.addresses() {
                         let peer = registration.record.peer_id();
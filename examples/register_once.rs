@@ -1,12 +1,14 @@
 use anyhow::Result;
 use libp2p::dns::TokioDnsConfig;
 use libp2p::futures::StreamExt;
-use libp2p::rendezvous::{Config, Namespace, Rendezvous};
+use libp2p::identify::{Identify, IdentifyConfig, IdentifyEvent};
+use libp2p::rendezvous::client::{Behaviour as Rendezvous, Event as RendezvousEvent};
+use libp2p::rendezvous::Namespace;
 use libp2p::swarm::{AddressScore, SwarmBuilder, SwarmEvent};
 use libp2p::tcp::TokioTcpConfig;
-use libp2p::{identity, rendezvous, Multiaddr, PeerId, Transport};
+use libp2p::{identity, Multiaddr, NetworkBehaviour, PeerId, Transport};
+use rendezvous_server::load_secret_key_from_file;
 use rendezvous_server::transport::authenticate_and_multiplex;
-use rendezvous_server::{load_secret_key_from_file, Behaviour, Event};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -16,11 +18,6 @@ struct Cli {
     rendezvous_peer_id: PeerId,
     #[structopt(long = "rendezvous-addr")]
     rendezvous_addr: Multiaddr,
-    #[structopt(
-        long = "external-addr",
-        help = "A public facing address is registered with the rendezvous server"
-    )]
-    external_addr: Multiaddr,
     #[structopt(
         long = "secret-file",
         help = "Path to the file that contains the secret used to derive the rendezvous server's identity"
@@ -45,11 +42,15 @@ async fn main() -> Result<()> {
 
     let transport = authenticate_and_multiplex(tcp_with_dns.boxed(), &identity).unwrap();
 
-    let rendezvous = Rendezvous::new(identity.clone(), Config::default());
+    let identify = Identify::new(IdentifyConfig::new(
+        "rendezvous-example/1.0.0".to_string(),
+        identity.public(),
+    ));
+    let rendezvous = Rendezvous::new(identity.clone());
 
     let peer_id = PeerId::from(identity.public());
 
-    let mut swarm = SwarmBuilder::new(transport, Behaviour::new(rendezvous), peer_id)
+    let mut swarm = SwarmBuilder::new(transport, Behaviour { identify, rendezvous }, peer_id)
         .executor(Box::new(|f| {
             tokio::spawn(f);
         }))
@@ -59,8 +60,6 @@ async fn main() -> Result<()> {
 
     let _ = swarm.listen_on(format!("/ip4/0.0.0.0/tcp/{}", cli.port).parse().unwrap());
 
-    let _ = swarm.add_external_address(cli.external_addr, AddressScore::Infinite);
-
     swarm.dial_addr(rendezvous_point_address).unwrap();
 
     while let Some(event) = swarm.next().await {
@@ -75,16 +74,21 @@ async fn main() -> Result<()> {
             } if peer_id == rendezvous_point => {
                 println!("Lost connection to rendezvous point {}", error);
             }
-            SwarmEvent::ConnectionEstablished { peer_id, .. } => {
-                if peer_id == cli.rendezvous_peer_id {
-                    swarm.behaviour_mut().rendezvous.register(
-                        Namespace::new("rendezvous".to_string())?,
-                        rendezvous_point,
-                        None,
-                    );
-                }
+            SwarmEvent::Behaviour(Event::Identify(IdentifyEvent::Received { peer_id, info }))
+                if peer_id == rendezvous_point =>
+            {
+                println!(
+                    "Rendezvous point observed us at {}, registering",
+                    info.observed_addr
+                );
+                swarm.add_external_address(info.observed_addr, AddressScore::Infinite);
+                swarm.behaviour_mut().rendezvous.register(
+                    Namespace::new("rendezvous".to_string())?,
+                    rendezvous_point,
+                    None,
+                );
             }
-            SwarmEvent::Behaviour(Event::Rendezvous(rendezvous::Event::Registered {
+            SwarmEvent::Behaviour(Event::Rendezvous(RendezvousEvent::Registered {
                 namespace,
                 ttl,
                 rendezvous_node,
@@ -95,7 +99,7 @@ async fn main() -> Result<()> {
                 );
                 return Ok(());
             }
-            SwarmEvent::Behaviour(Event::Rendezvous(rendezvous::Event::RegisterFailed(error))) => {
+            SwarmEvent::Behaviour(Event::Rendezvous(RendezvousEvent::RegisterFailed(error))) => {
                 println!("Failed to register {:?}", error);
             }
             other => {
@@ -106,3 +110,29 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[derive(Debug)]
+enum Event {
+    Rendezvous(RendezvousEvent),
+    Identify(IdentifyEvent),
+}
+
+impl From<RendezvousEvent> for Event {
+    fn from(event: RendezvousEvent) -> Self {
+        Event::Rendezvous(event)
+    }
+}
+
+impl From<IdentifyEvent> for Event {
+    fn from(event: IdentifyEvent) -> Self {
+        Event::Identify(event)
+    }
+}
+
+#[derive(NetworkBehaviour)]
+#[behaviour(event_process = false)]
+#[behaviour(out_event = "Event")]
+struct Behaviour {
+    identify: Identify,
+    rendezvous: Rendezvous,
+}
@@ -0,0 +1,141 @@
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use libp2p::rendezvous::Namespace;
+use libp2p::{Multiaddr, PeerId};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone)]
+struct RegistrationInfo {
+    addresses: Vec<Multiaddr>,
+    ttl: i64,
+    registered_at: SystemTime,
+}
+
+#[derive(Serialize)]
+struct RegistrationInfoJson {
+    peer: String,
+    addresses: Vec<String>,
+    ttl: i64,
+    registered_at_unix: u64,
+}
+
+/// In-memory mirror of the rendezvous store, driven purely by
+/// `PeerRegistered`/`RegistrationExpired`/`PeerUnregistered` swarm events so
+/// it stays consistent with the rendezvous store's own TTL expiry.
+#[derive(Default)]
+pub struct Registry {
+    namespaces: Mutex<HashMap<Namespace, HashMap<PeerId, RegistrationInfo>>>,
+}
+
+impl Registry {
+    pub fn insert(&self, namespace: Namespace, peer: PeerId, addresses: Vec<Multiaddr>, ttl: i64) {
+        self.namespaces
+            .lock()
+            .unwrap()
+            .entry(namespace)
+            .or_default()
+            .insert(
+                peer,
+                RegistrationInfo {
+                    addresses,
+                    ttl,
+                    registered_at: SystemTime::now(),
+                },
+            );
+    }
+
+    pub fn remove(&self, namespace: &Namespace, peer: &PeerId) {
+        if let Some(peers) = self.namespaces.lock().unwrap().get_mut(namespace) {
+            peers.remove(peer);
+        }
+    }
+
+    fn snapshot(&self, namespace: Option<&Namespace>) -> HashMap<String, Vec<RegistrationInfoJson>> {
+        self.namespaces
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(ns, _)| namespace.map_or(true, |wanted| *ns == wanted))
+            .map(|(ns, peers)| {
+                let peers = peers
+                    .iter()
+                    .map(|(peer, info)| RegistrationInfoJson {
+                        peer: peer.to_string(),
+                        addresses: info.addresses.iter().map(Multiaddr::to_string).collect(),
+                        ttl: info.ttl,
+                        registered_at_unix: info
+                            .registered_at
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or(Duration::ZERO)
+                            .as_secs(),
+                    })
+                    .collect();
+                (ns.to_string(), peers)
+            })
+            .collect()
+    }
+}
+
+/// Serves the registry as read-only JSON: `/registrations` for everything,
+/// `/registrations/{namespace}` filtered to a single namespace.
+pub async fn serve(registry: Arc<Registry>, port: u16) -> Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+    let make_svc = make_service_fn(move |_conn| {
+        let registry = registry.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let registry = registry.clone();
+                async move { Ok::<_, Infallible>(handle(&registry, req)) }
+            }))
+        }
+    });
+
+    Server::bind(&addr).serve(make_svc).await?;
+
+    Ok(())
+}
+
+fn handle(registry: &Registry, req: Request<Body>) -> Response<Body> {
+    if req.method() != Method::GET {
+        return Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty())
+            .expect("static response is valid");
+    }
+
+    let mut segments = req.uri().path().trim_matches('/').split('/');
+    match (segments.next(), segments.next()) {
+        (Some("registrations"), None) => json_response(&registry.snapshot(None)),
+        (Some("registrations"), Some(namespace)) => match Namespace::new(namespace.to_string()) {
+            Ok(namespace) => json_response(&registry.snapshot(Some(&namespace))),
+            Err(_) => Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::empty())
+                .expect("static response is valid"),
+        },
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .expect("static response is valid"),
+    }
+}
+
+fn json_response(value: &impl Serialize) -> Response<Body> {
+    match serde_json::to_vec(value) {
+        Ok(body) => Response::builder()
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .expect("static response is valid"),
+        Err(_) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::empty())
+            .expect("static response is valid"),
+    }
+}
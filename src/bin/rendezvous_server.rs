@@ -1,14 +1,19 @@
 use anyhow::Result;
 use libp2p::dns::TokioDnsConfig;
 use libp2p::futures::StreamExt;
-use libp2p::rendezvous::{Config, Event as RendezvousEvent, Rendezvous};
+use libp2p::identify::IdentifyEvent;
+use libp2p::ping::PingEvent;
+use libp2p::rendezvous::server::{Behaviour as Rendezvous, Config, Event as RendezvousEvent};
 use libp2p::swarm::{SwarmBuilder, SwarmEvent};
 use libp2p::tcp::TokioTcpConfig;
+use libp2p::websocket::WsConfig;
 use libp2p::{identity, PeerId, Transport};
+use rendezvous_server::admin;
 use rendezvous_server::tracing::init;
 use rendezvous_server::transport::authenticate_and_multiplex;
 use rendezvous_server::{generate_secret_key_file, load_secret_key_from_file, Behaviour, Event};
 use std::path::PathBuf;
+use std::sync::Arc;
 use structopt::StructOpt;
 use tracing::level_filters::LevelFilter;
 
@@ -31,6 +36,37 @@ struct Cli {
     pub json: bool,
     #[structopt(long = "timestamp", help = "Include timestamp in logs")]
     pub timestamp: bool,
+    #[structopt(
+        long = "websocket",
+        help = "Also listen for WebSocket connections. Note: this is plain ws://, not wss:// — there is no TLS wiring here, so browser clients served over https will refuse to connect"
+    )]
+    websocket: bool,
+    #[structopt(
+        long = "websocket-port",
+        required_if("websocket", "true"),
+        help = "Port used for listening on WebSocket"
+    )]
+    websocket_port: Option<u16>,
+    #[structopt(
+        long = "admin-port",
+        help = "Serve a read-only JSON registration registry on this port"
+    )]
+    admin_port: Option<u16>,
+    #[structopt(
+        long = "min-ttl",
+        help = "Reject registrations that request a TTL (in seconds) below this bound"
+    )]
+    min_ttl: Option<u64>,
+    #[structopt(
+        long = "max-ttl",
+        help = "Reject registrations that request a TTL (in seconds) above this bound"
+    )]
+    max_ttl: Option<u64>,
+    #[structopt(
+        long = "default-ttl",
+        help = "TTL (in seconds) assumed for registrations that do not specify one"
+    )]
+    default_ttl: Option<u64>,
 }
 
 #[tokio::main]
@@ -48,17 +84,36 @@ async fn main() -> Result<()> {
 
     let tcp_with_dns = TokioDnsConfig::system(TokioTcpConfig::new().nodelay(true)).unwrap();
 
-    let transport = authenticate_and_multiplex(tcp_with_dns.boxed(), &identity).unwrap();
+    let transport = if cli.websocket {
+        let websocket_with_dns = WsConfig::new(tcp_with_dns.clone());
+        authenticate_and_multiplex(tcp_with_dns.or_transport(websocket_with_dns).boxed(), &identity).unwrap()
+    } else {
+        authenticate_and_multiplex(tcp_with_dns.boxed(), &identity).unwrap()
+    };
 
-    let rendezvous = Rendezvous::new(identity.clone(), Config::default());
+    let mut rendezvous_config = Config::default();
+    if let Some(min_ttl) = cli.min_ttl {
+        rendezvous_config = rendezvous_config.with_min_ttl(min_ttl);
+    }
+    if let Some(max_ttl) = cli.max_ttl {
+        rendezvous_config = rendezvous_config.with_max_ttl(max_ttl);
+    }
+    if let Some(default_ttl) = cli.default_ttl {
+        rendezvous_config = rendezvous_config.with_default_ttl(default_ttl);
+    }
+    let rendezvous = Rendezvous::new(identity.clone(), rendezvous_config);
 
     let peer_id = PeerId::from(identity.public());
 
-    let mut swarm = SwarmBuilder::new(transport, Behaviour::new(rendezvous), peer_id)
-        .executor(Box::new(|f| {
-            tokio::spawn(f);
-        }))
-        .build();
+    let mut swarm = SwarmBuilder::new(
+        transport,
+        Behaviour::new(rendezvous, identity.public()),
+        peer_id,
+    )
+    .executor(Box::new(|f| {
+        tokio::spawn(f);
+    }))
+    .build();
 
     tracing::info!(peer_id=%swarm.local_peer_id(), "Rendezvous server peer id");
 
@@ -66,6 +121,22 @@ async fn main() -> Result<()> {
         .listen_on(format!("/ip4/0.0.0.0/tcp/{}", cli.port).parse().unwrap())
         .unwrap();
 
+    if let Some(websocket_port) = cli.websocket_port {
+        swarm
+            .listen_on(format!("/ip4/0.0.0.0/tcp/{}/ws", websocket_port).parse().unwrap())
+            .unwrap();
+    }
+
+    let registry = Arc::new(admin::Registry::default());
+    if let Some(admin_port) = cli.admin_port {
+        let registry = registry.clone();
+        tokio::spawn(async move {
+            if let Err(e) = admin::serve(registry, admin_port).await {
+                tracing::error!(%e, "Admin server exited");
+            }
+        });
+    }
+
     loop {
         let event = swarm.next().await;
 
@@ -75,6 +146,12 @@ async fn main() -> Result<()> {
                     peer,
                     registration,
                 })) => {
+                    registry.insert(
+                        registration.namespace.clone(),
+                        peer,
+                        registration.record.addresses().to_vec(),
+                        registration.ttl,
+                    );
                     tracing::info!(%peer, namespace=%registration.namespace, addresses=?registration.record.addresses(), ttl=registration.ttl,  "Peer registered");
                 }
                 SwarmEvent::Behaviour(Event::Rendezvous(RendezvousEvent::PeerNotRegistered {
@@ -87,14 +164,29 @@ async fn main() -> Result<()> {
                 SwarmEvent::Behaviour(Event::Rendezvous(RendezvousEvent::RegistrationExpired(
                     registration,
                 ))) => {
+                    registry.remove(&registration.namespace, &registration.record.peer_id());
                     tracing::info!(peer=%registration.record.peer_id(), namespace=%registration.namespace, addresses=%rendezvous_server::tracing::Addresses(registration.record.addresses()), ttl=registration.ttl, "Registration expired");
                 }
                 SwarmEvent::Behaviour(Event::Rendezvous(RendezvousEvent::PeerUnregistered {
                     peer,
                     namespace,
                 })) => {
+                    registry.remove(&namespace, &peer);
                     tracing::info!(%peer, %namespace, "Peer unregistered");
                 }
+                SwarmEvent::Behaviour(Event::Identify(IdentifyEvent::Received {
+                    peer_id,
+                    info,
+                })) => {
+                    tracing::info!(peer=%peer_id, agent_version=%info.agent_version, observed_addr=%info.observed_addr, "Received identify info");
+                }
+                SwarmEvent::Behaviour(Event::Ping(PingEvent {
+                    peer,
+                    result: Err(failure),
+                })) => {
+                    tracing::warn!(%peer, %failure, "Ping failed, disconnecting peer");
+                    let _ = swarm.disconnect_peer_id(peer);
+                }
                 _ => {}
             }
         }
@@ -1,8 +1,11 @@
 use anyhow::{Context, Result};
+use libp2p::identify::{Identify, IdentifyConfig, IdentifyEvent};
 use libp2p::identity::ed25519::{Keypair, SecretKey};
+use libp2p::identity::PublicKey;
 use libp2p::ping::{Ping, PingConfig, PingEvent};
-use libp2p::rendezvous::Rendezvous;
-use libp2p::{rendezvous, NetworkBehaviour};
+use libp2p::rendezvous::server;
+use libp2p::rendezvous::server::Behaviour as Rendezvous;
+use libp2p::NetworkBehaviour;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
@@ -10,20 +13,28 @@ use tokio::fs;
 use tokio::fs::{DirBuilder, OpenOptions};
 use tokio::io::AsyncWriteExt;
 
+pub mod admin;
 pub mod transport;
 
 #[derive(Debug)]
 pub enum Event {
-    Rendezvous(rendezvous::Event),
+    Rendezvous(server::Event),
+    Identify(IdentifyEvent),
     Ping(PingEvent),
 }
 
-impl From<rendezvous::Event> for Event {
-    fn from(event: rendezvous::Event) -> Self {
+impl From<server::Event> for Event {
+    fn from(event: server::Event) -> Self {
         Event::Rendezvous(event)
     }
 }
 
+impl From<IdentifyEvent> for Event {
+    fn from(event: IdentifyEvent) -> Self {
+        Event::Identify(event)
+    }
+}
+
 impl From<PingEvent> for Event {
     fn from(event: PingEvent) -> Self {
         Event::Ping(event)
@@ -35,11 +46,12 @@ impl From<PingEvent> for Event {
 #[behaviour(out_event = "Event")]
 pub struct Behaviour {
     ping: Ping,
+    identify: Identify,
     pub rendezvous: Rendezvous,
 }
 
 impl Behaviour {
-    pub fn new(rendezvous: Rendezvous) -> Self {
+    pub fn new(rendezvous: Rendezvous, public_key: PublicKey) -> Self {
         Self {
             // TODO: Remove Ping behaviour once https://github.com/libp2p/rust-libp2p/issues/2109 is fixed
             // interval for sending Ping set to 24 hours
@@ -48,6 +60,10 @@ impl Behaviour {
                     .with_keep_alive(false)
                     .with_interval(Duration::from_secs(86_400)),
             ),
+            identify: Identify::new(IdentifyConfig::new(
+                "rendezvous-server/1.0.0".to_string(),
+                public_key,
+            )),
             rendezvous,
         }
     }
@@ -1,22 +1,17 @@
 use anyhow::{Context, Result};
-use futures::{AsyncRead, AsyncWrite, StreamExt};
-use libp2p::core::muxing::StreamMuxerBox;
-use libp2p::core::transport::Boxed;
-use libp2p::core::upgrade::{SelectUpgrade, Version};
-use libp2p::dns::TokioDnsConfig;
+use futures::StreamExt;
+use libp2p::bandwidth::BandwidthSinks;
+use libp2p::identify::{Identify, IdentifyConfig, IdentifyEvent};
 use libp2p::identity::ed25519;
-use libp2p::mplex::MplexConfig;
-use libp2p::noise::{NoiseConfig, X25519Spec};
 use libp2p::ping::{Ping, PingConfig, PingEvent};
-use libp2p::rendezvous::{Config, Event as RendezvousEvent, Rendezvous};
+use libp2p::rendezvous::server::{Behaviour as Rendezvous, Config, Event as RendezvousEvent};
 use libp2p::swarm::toggle::Toggle;
-use libp2p::swarm::{SwarmBuilder, SwarmEvent};
-use libp2p::tcp::TokioTcpConfig;
-use libp2p::websocket::WsConfig;
-use libp2p::yamux::YamuxConfig;
-use libp2p::{identity, noise, rendezvous, Multiaddr, PeerId, Swarm, Transport};
+use libp2p::swarm::{ConnectionLimits, SwarmBuilder, SwarmEvent};
+use libp2p::rendezvous::server;
+use libp2p::{identity, Multiaddr, Swarm};
 use std::fmt;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 use structopt::StructOpt;
 use tokio::fs;
@@ -26,6 +21,10 @@ use tracing::level_filters::LevelFilter;
 use tracing_subscriber::fmt::time::ChronoLocal;
 use tracing_subscriber::FmtSubscriber;
 
+mod metrics;
+mod registry;
+mod transport;
+
 #[derive(Debug, StructOpt)]
 struct Cli {
     /// Path to the file that contains the secret key of the rendezvous server's
@@ -56,6 +55,53 @@ struct Cli {
     /// Port used for listening on websocket
     #[structopt(long, required_if("websocket", "true"))]
     websocket_port: u16,
+    /// The maximum number of simultaneous connections the server will accept.
+    /// Unset means unlimited.
+    #[structopt(long)]
+    max_connections: Option<u32>,
+    /// The maximum number of simultaneous connections to a single peer the
+    /// server will accept. Defaults to 1 because a single registration
+    /// connection is all a well-behaved client needs.
+    #[structopt(long, default_value = "1")]
+    max_connections_per_peer: u32,
+    /// The maximum number of pending incoming connections the server will
+    /// accept. Unset means unlimited.
+    #[structopt(long)]
+    max_pending_incoming: Option<u32>,
+    /// The maximum number of established incoming connections the server
+    /// will accept. Unset means unlimited.
+    #[structopt(long)]
+    max_established_incoming: Option<u32>,
+    /// Serve Prometheus metrics on this port. If unset, no metrics server is
+    /// started.
+    #[structopt(long)]
+    metrics_port: Option<u16>,
+    /// Dump the current registration table to stdout whenever the process
+    /// receives SIGUSR1. Honours the --json flag for the output format.
+    #[structopt(long)]
+    dump_registrations_on_signal: bool,
+    /// Reject registrations that request a TTL (in seconds) below this
+    /// bound. Unset falls back to the rendezvous protocol's default.
+    #[structopt(long)]
+    min_ttl: Option<u64>,
+    /// Reject registrations that request a TTL (in seconds) above this
+    /// bound. Unset falls back to the rendezvous protocol's default.
+    #[structopt(long)]
+    max_ttl: Option<u64>,
+    /// Log a warning for every registration once the total number of stored
+    /// registrations exceeds this threshold.
+    ///
+    /// NOTE: this does not cap registrations, despite that being the original
+    /// ask for this flag. `rendezvous::server::Config`/`Behaviour` expose no
+    /// hook to reject or evict a registration by count — registration
+    /// acceptance is decided entirely inside the protocol's message handling,
+    /// before this binary ever sees the peer. Enforcing a hard cap would mean
+    /// forking the rendezvous protocol implementation, which is out of scope
+    /// here. As written, "cap total stored records" is not satisfiable
+    /// against the upstream library; this flag is intentionally advisory-only
+    /// rather than a weakened version of that request.
+    #[structopt(long = "max-registrations-warning")]
+    max_registrations_warning: Option<usize>,
 }
 
 #[tokio::main]
@@ -75,7 +121,21 @@ async fn main() -> Result<()> {
     };
     let identity = identity::Keypair::Ed25519(secret_key.into());
 
-    let mut swarm = create_swarm(identity, cli.ping, cli.websocket)?;
+    let connection_limits = ConnectionLimits::default()
+        .with_max_established_per_peer(Some(cli.max_connections_per_peer))
+        .with_max_established_incoming(cli.max_established_incoming)
+        .with_max_pending_incoming(cli.max_pending_incoming)
+        .with_max_established(cli.max_connections);
+
+    let (mut swarm, bandwidth_sinks) =
+        create_swarm(
+            identity,
+            cli.ping,
+            cli.websocket,
+            connection_limits,
+            cli.min_ttl,
+            cli.max_ttl,
+        )?;
 
     tracing::info!(peer_id=%swarm.local_peer_id(), "Rendezvous server peer id");
 
@@ -97,12 +157,56 @@ async fn main() -> Result<()> {
             .context("Failed to initialize websocket listener")?;
     }
 
+    let registration_table = Arc::new(registry::RegistrationTable::default());
+    if cli.dump_registrations_on_signal {
+        let registration_table = registration_table.clone();
+        let json = cli.json;
+        let mut sigusr1 = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1())
+            .context("Failed to install SIGUSR1 handler")?;
+        tokio::spawn(async move {
+            while sigusr1.recv().await.is_some() {
+                if json {
+                    println!("{}", registration_table.dump_json());
+                } else {
+                    print!("{}", registration_table.dump_human());
+                }
+            }
+        });
+    }
+
+    let metrics = metrics::Metrics::new()?;
+    if let Some(metrics_port) = cli.metrics_port {
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(metrics, metrics_port).await {
+                tracing::error!(%e, "Metrics server exited");
+            }
+        });
+    }
+
+    let mut bandwidth_report_interval = tokio::time::interval(Duration::from_secs(60));
+    let mut last_inbound = 0;
+    let mut last_outbound = 0;
+
     loop {
-        match swarm.select_next_some().await {
+        tokio::select! {
+            event = swarm.select_next_some() => match event {
             SwarmEvent::Behaviour(Event::Rendezvous(RendezvousEvent::PeerRegistered {
                 peer,
                 registration,
             })) => {
+                let is_new = registration_table.insert(
+                    registration.namespace.clone(),
+                    peer,
+                    registration.record.addresses().to_vec(),
+                    registration.ttl,
+                );
+                metrics.record_registration(&registration.namespace, is_new);
+                if let Some(max_registrations_warning) = cli.max_registrations_warning {
+                    if registration_table.len() > max_registrations_warning {
+                        tracing::warn!(max_registrations_warning, current = registration_table.len(), "Registration count exceeds warning threshold");
+                    }
+                }
                 tracing::info!(%peer, namespace=%registration.namespace, addresses=?registration.record.addresses(), ttl=registration.ttl,  "Peer registered");
             }
             SwarmEvent::Behaviour(Event::Rendezvous(RendezvousEvent::PeerNotRegistered {
@@ -110,29 +214,59 @@ async fn main() -> Result<()> {
                 namespace,
                 error,
             })) => {
+                metrics.record_registration_failure();
                 tracing::info!(%peer, %namespace, ?error, "Peer failed to register");
             }
             SwarmEvent::Behaviour(Event::Rendezvous(RendezvousEvent::RegistrationExpired(
                 registration,
             ))) => {
+                metrics.record_expiry_or_unregister(&registration.namespace);
+                registration_table.remove(&registration.namespace, &registration.record.peer_id());
                 tracing::info!(peer=%registration.record.peer_id(), namespace=%registration.namespace, addresses=%Addresses(registration.record.addresses()), ttl=registration.ttl, "Registration expired");
             }
             SwarmEvent::Behaviour(Event::Rendezvous(RendezvousEvent::PeerUnregistered {
                 peer,
                 namespace,
             })) => {
+                metrics.record_expiry_or_unregister(&namespace);
+                registration_table.remove(&namespace, &peer);
                 tracing::info!(%peer, %namespace, "Peer unregistered");
             }
             SwarmEvent::Behaviour(Event::Rendezvous(RendezvousEvent::DiscoverServed {
                 enquirer,
                 ..
             })) => {
+                metrics.record_discovery_served();
                 tracing::info!(peer=%enquirer, "Discovery served");
             }
             SwarmEvent::NewListenAddr(address) => {
                 tracing::info!(%address, "New listening address reported");
             }
+            SwarmEvent::IncomingConnectionError {
+                send_back_addr,
+                error: libp2p::swarm::PendingConnectionError::ConnectionLimit(limit),
+                ..
+            } => {
+                tracing::warn!(%send_back_addr, limit = limit.limit, current = limit.current, "Refused incoming connection due to connection limits");
+            }
+            SwarmEvent::Behaviour(Event::Identify(IdentifyEvent::Received { peer_id, info })) => {
+                tracing::info!(peer=%peer_id, agent_version=%info.agent_version, protocol_version=%info.protocol_version, observed_addr=%info.observed_addr, "Received identify info");
+            }
             _ => {}
+            },
+            _ = bandwidth_report_interval.tick() => {
+                let inbound = bandwidth_sinks.total_inbound();
+                let outbound = bandwidth_sinks.total_outbound();
+                tracing::info!(
+                    total_inbound_bytes = inbound,
+                    total_outbound_bytes = outbound,
+                    inbound_bytes_per_sec = (inbound - last_inbound) / 60,
+                    outbound_bytes_per_sec = (outbound - last_outbound) / 60,
+                    "Bandwidth report"
+                );
+                last_inbound = inbound;
+                last_outbound = outbound;
+            }
         }
     }
 }
@@ -202,76 +336,59 @@ fn create_swarm(
     identity: identity::Keypair,
     ping: bool,
     websocket: bool,
-) -> Result<Swarm<Behaviour>> {
+    connection_limits: ConnectionLimits,
+    min_ttl: Option<u64>,
+    max_ttl: Option<u64>,
+) -> Result<(Swarm<Behaviour>, Arc<BandwidthSinks>)> {
     let local_peer_id = identity.public().into_peer_id();
 
-    let transport = create_transport(&identity, websocket).context("Failed to create transport")?;
-    let rendezvous = Rendezvous::new(identity, Config::default());
-    let swarm = SwarmBuilder::new(transport, Behaviour::new(rendezvous, ping), local_peer_id)
+    let (transport, bandwidth_sinks) =
+        transport::create_transport(&identity, websocket).context("Failed to create transport")?;
+    let identify = Identify::new(IdentifyConfig::new(
+        "rendezvous-server/1.0.0".to_string(),
+        identity.public(),
+    ));
+    let mut rendezvous_config = Config::default();
+    if let Some(min_ttl) = min_ttl {
+        rendezvous_config = rendezvous_config.with_min_ttl(min_ttl);
+    }
+    if let Some(max_ttl) = max_ttl {
+        rendezvous_config = rendezvous_config.with_max_ttl(max_ttl);
+    }
+    let rendezvous = Rendezvous::new(identity, rendezvous_config);
+    let swarm = SwarmBuilder::new(
+        transport,
+        Behaviour::new(rendezvous, identify, ping),
+        local_peer_id,
+    )
+        .connection_limits(connection_limits)
         .executor(Box::new(|f| {
             tokio::spawn(f);
         }))
         .build();
 
-    Ok(swarm)
-}
-
-fn create_transport(
-    identity: &identity::Keypair,
-    websocket: bool,
-) -> Result<Boxed<(PeerId, StreamMuxerBox)>> {
-    let tcp_with_dns = TokioDnsConfig::system(TokioTcpConfig::new().nodelay(true)).unwrap();
-
-    let transport = if websocket {
-        let websocket_with_dns = WsConfig::new(tcp_with_dns.clone());
-        authenticate_and_multiplex(
-            tcp_with_dns.or_transport(websocket_with_dns).boxed(),
-            &identity,
-        )
-        .unwrap()
-    } else {
-        authenticate_and_multiplex(tcp_with_dns.boxed(), &identity).unwrap()
-    };
-
-    Ok(transport)
-}
-
-fn authenticate_and_multiplex<T>(
-    transport: Boxed<T>,
-    identity: &identity::Keypair,
-) -> Result<Boxed<(PeerId, StreamMuxerBox)>>
-where
-    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
-{
-    let auth_upgrade = {
-        let noise_identity = noise::Keypair::<X25519Spec>::new().into_authentic(identity)?;
-        NoiseConfig::xx(noise_identity).into_authenticated()
-    };
-    let multiplex_upgrade = SelectUpgrade::new(YamuxConfig::default(), MplexConfig::new());
-
-    let transport = transport
-        .upgrade(Version::V1)
-        .authenticate(auth_upgrade)
-        .multiplex(multiplex_upgrade)
-        .timeout(Duration::from_secs(20))
-        .map(|(peer, muxer), _| (peer, StreamMuxerBox::new(muxer)))
-        .boxed();
-
-    Ok(transport)
+    Ok((swarm, bandwidth_sinks))
 }
 
 #[derive(Debug)]
 enum Event {
-    Rendezvous(rendezvous::Event),
+    Rendezvous(server::Event),
+    Identify(IdentifyEvent),
     Ping(PingEvent),
 }
 
-impl From<rendezvous::Event> for Event {
-    fn from(event: rendezvous::Event) -> Self {
+impl From<server::Event> for Event {
+    fn from(event: server::Event) -> Self {
         Event::Rendezvous(event)
     }
 }
 
+impl From<IdentifyEvent> for Event {
+    fn from(event: IdentifyEvent) -> Self {
+        Event::Identify(event)
+    }
+}
+
 impl From<PingEvent> for Event {
     fn from(event: PingEvent) -> Self {
         Event::Ping(event)
@@ -283,11 +400,12 @@ impl From<PingEvent> for Event {
 #[behaviour(out_event = "Event")]
 struct Behaviour {
     ping: Toggle<Ping>,
+    identify: Identify,
     rendezvous: Rendezvous,
 }
 
 impl Behaviour {
-    fn new(rendezvous: Rendezvous, ping: bool) -> Self {
+    fn new(rendezvous: Rendezvous, identify: Identify, ping: bool) -> Self {
         let ping = if ping {
             Toggle::from(Some(Ping::new(
                 PingConfig::new()
@@ -302,12 +420,13 @@ impl Behaviour {
             // TODO: Remove Ping behaviour once https://github.com/libp2p/rust-libp2p/issues/2109 is fixed
             // interval for sending Ping set to 24 hours
             ping,
+            identify,
             rendezvous,
         }
     }
 }
 
-struct Addresses<'a>(&'a [Multiaddr]);
+pub(crate) struct Addresses<'a>(&'a [Multiaddr]);
 
 // Prints an array of multiaddresses as a comma seperated string
 impl fmt::Display for Addresses<'_> {
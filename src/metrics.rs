@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use libp2p::rendezvous::Namespace;
+use prometheus::{Encoder, IntCounter, IntGaugeVec, Opts, Registry, TextEncoder};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+/// Registration-related counters and gauges, served over HTTP in Prometheus
+/// text exposition format. Cheap to clone; every handle shares the same
+/// underlying series.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    registrations_total: IntCounter,
+    registration_failures_total: IntCounter,
+    discoveries_served_total: IntCounter,
+    active_registrations: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new();
+
+        let registrations_total = IntCounter::new(
+            "rendezvous_registrations_total",
+            "Total number of successful peer registrations",
+        )?;
+        let registration_failures_total = IntCounter::new(
+            "rendezvous_registration_failures_total",
+            "Total number of peer registrations rejected by the server",
+        )?;
+        let discoveries_served_total = IntCounter::new(
+            "rendezvous_discoveries_served_total",
+            "Total number of discovery requests served",
+        )?;
+        let active_registrations = IntGaugeVec::new(
+            Opts::new(
+                "rendezvous_active_registrations",
+                "Number of currently active registrations, labelled by namespace",
+            ),
+            &["namespace"],
+        )?;
+
+        registry.register(Box::new(registrations_total.clone()))?;
+        registry.register(Box::new(registration_failures_total.clone()))?;
+        registry.register(Box::new(discoveries_served_total.clone()))?;
+        registry.register(Box::new(active_registrations.clone()))?;
+
+        Ok(Self {
+            registry,
+            registrations_total,
+            registration_failures_total,
+            discoveries_served_total,
+            active_registrations,
+        })
+    }
+
+    /// Records a successful registration. `is_new` must be `false` for a
+    /// refresh of an already-registered peer (the rendezvous protocol fires
+    /// `PeerRegistered` again on every re-registration), otherwise
+    /// `active_registrations` would count refreshes as new registrations
+    /// and drift upward forever.
+    pub fn record_registration(&self, namespace: &Namespace, is_new: bool) {
+        self.registrations_total.inc();
+        if is_new {
+            self.active_registrations
+                .with_label_values(&[namespace.as_ref()])
+                .inc();
+        }
+    }
+
+    pub fn record_registration_failure(&self) {
+        self.registration_failures_total.inc();
+    }
+
+    pub fn record_expiry_or_unregister(&self, namespace: &Namespace) {
+        self.active_registrations
+            .with_label_values(&[namespace.as_ref()])
+            .dec();
+    }
+
+    pub fn record_discovery_served(&self) {
+        self.discoveries_served_total.inc();
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let metric_families = self.registry.gather();
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&metric_families, &mut buffer)
+            .expect("encoding to a Vec<u8> cannot fail");
+        buffer
+    }
+}
+
+/// Runs a minimal HTTP server that serves the current metrics in Prometheus
+/// text exposition format on every request, regardless of path.
+pub async fn serve(metrics: Metrics, port: u16) -> Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+    let make_svc = make_service_fn(move |_conn| {
+        let metrics = metrics.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |_req: Request<Body>| {
+                let metrics = metrics.clone();
+                async move { Ok::<_, Infallible>(Response::new(Body::from(metrics.encode()))) }
+            }))
+        }
+    });
+
+    Server::bind(&addr)
+        .serve(make_svc)
+        .await
+        .context("Metrics server failed")?;
+
+    Ok(())
+}
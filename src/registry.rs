@@ -0,0 +1,95 @@
+use libp2p::rendezvous::Namespace;
+use libp2p::{Multiaddr, PeerId};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::Addresses;
+
+struct Entry {
+    addresses: Vec<Multiaddr>,
+    expires_at: Instant,
+}
+
+/// Mirrors the rendezvous store's registration state so it can be dumped on
+/// demand without having to grep logs. Kept up to date purely by the same
+/// `PeerRegistered`/`RegistrationExpired`/`PeerUnregistered` events the main
+/// loop already logs.
+#[derive(Default)]
+pub struct RegistrationTable {
+    namespaces: Mutex<HashMap<Namespace, HashMap<PeerId, Entry>>>,
+}
+
+impl RegistrationTable {
+    /// Inserts or refreshes a registration, returning `true` if the peer was
+    /// not already registered in this namespace (as opposed to a TTL
+    /// refresh of an existing registration).
+    pub fn insert(&self, namespace: Namespace, peer: PeerId, addresses: Vec<Multiaddr>, ttl: i64) -> bool {
+        let expires_at = Instant::now() + Duration::from_secs(ttl.max(0) as u64);
+        self.namespaces
+            .lock()
+            .unwrap()
+            .entry(namespace)
+            .or_default()
+            .insert(peer, Entry { addresses, expires_at })
+            .is_none()
+    }
+
+    pub fn remove(&self, namespace: &Namespace, peer: &PeerId) {
+        if let Some(peers) = self.namespaces.lock().unwrap().get_mut(namespace) {
+            peers.remove(peer);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.namespaces.lock().unwrap().values().map(HashMap::len).sum()
+    }
+
+    fn rows(&self) -> Vec<(Namespace, PeerId, Vec<Multiaddr>, i64)> {
+        let now = Instant::now();
+        self.namespaces
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|(namespace, peers)| {
+                peers.iter().map(move |(peer, entry)| {
+                    let seconds_to_expiry = entry
+                        .expires_at
+                        .saturating_duration_since(now)
+                        .as_secs() as i64;
+                    (namespace.clone(), *peer, entry.addresses.clone(), seconds_to_expiry)
+                })
+            })
+            .collect()
+    }
+
+    pub fn dump_human(&self) -> String {
+        let mut output = String::from("namespace\tpeer\taddresses\tseconds-to-expiry\n");
+        for (namespace, peer, addresses, seconds_to_expiry) in self.rows() {
+            output.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                namespace,
+                peer,
+                Addresses(&addresses),
+                seconds_to_expiry
+            ));
+        }
+        output
+    }
+
+    pub fn dump_json(&self) -> serde_json::Value {
+        serde_json::Value::Array(
+            self.rows()
+                .into_iter()
+                .map(|(namespace, peer, addresses, seconds_to_expiry)| {
+                    serde_json::json!({
+                        "namespace": namespace.to_string(),
+                        "peer": peer.to_string(),
+                        "addresses": addresses.iter().map(Multiaddr::to_string).collect::<Vec<_>>(),
+                        "seconds_to_expiry": seconds_to_expiry,
+                    })
+                })
+                .collect(),
+        )
+    }
+}
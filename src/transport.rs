@@ -0,0 +1,60 @@
+use anyhow::Result;
+use futures::{AsyncRead, AsyncWrite};
+use libp2p::bandwidth::{BandwidthLogging, BandwidthSinks};
+use libp2p::core::muxing::StreamMuxerBox;
+use libp2p::core::transport::Boxed;
+use libp2p::core::upgrade::{SelectUpgrade, Version};
+use libp2p::dns::TokioDnsConfig;
+use libp2p::mplex::MplexConfig;
+use libp2p::noise::{NoiseConfig, X25519Spec};
+use libp2p::tcp::TokioTcpConfig;
+use libp2p::websocket::WsConfig;
+use libp2p::yamux::YamuxConfig;
+use libp2p::{identity, noise, PeerId, Transport};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builds the transport stack used by the rendezvous server (TCP, optionally
+/// layered with WebSocket) and wraps it in a bandwidth meter so callers can
+/// read cumulative inbound/outbound byte counters off the returned sinks.
+pub fn create_transport(
+    identity: &identity::Keypair,
+    websocket: bool,
+) -> Result<(Boxed<(PeerId, StreamMuxerBox)>, Arc<BandwidthSinks>)> {
+    let tcp_with_dns = TokioDnsConfig::system(TokioTcpConfig::new().nodelay(true)).unwrap();
+
+    let transport = if websocket {
+        let websocket_with_dns = WsConfig::new(tcp_with_dns.clone());
+        authenticate_and_multiplex(tcp_with_dns.or_transport(websocket_with_dns).boxed(), identity)?
+    } else {
+        authenticate_and_multiplex(tcp_with_dns.boxed(), identity)?
+    };
+
+    let (transport, sinks) = BandwidthLogging::new(transport);
+
+    Ok((transport.boxed(), sinks))
+}
+
+pub fn authenticate_and_multiplex<T>(
+    transport: Boxed<T>,
+    identity: &identity::Keypair,
+) -> Result<Boxed<(PeerId, StreamMuxerBox)>>
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let auth_upgrade = {
+        let noise_identity = noise::Keypair::<X25519Spec>::new().into_authentic(identity)?;
+        NoiseConfig::xx(noise_identity).into_authenticated()
+    };
+    let multiplex_upgrade = SelectUpgrade::new(YamuxConfig::default(), MplexConfig::new());
+
+    let transport = transport
+        .upgrade(Version::V1)
+        .authenticate(auth_upgrade)
+        .multiplex(multiplex_upgrade)
+        .timeout(Duration::from_secs(20))
+        .map(|(peer, muxer), _| (peer, StreamMuxerBox::new(muxer)))
+        .boxed();
+
+    Ok(transport)
+}